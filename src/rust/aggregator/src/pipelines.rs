@@ -1,17 +1,25 @@
 pub mod candlesticks;
+pub mod coingecko;
 pub mod coins;
 pub mod leaderboards;
 pub mod markets;
+pub mod mempool;
 pub mod order_history;
+pub mod recent_activity;
 pub mod refresh_materialized_view;
 pub mod rolling_volume;
 pub mod user_history;
+pub mod ws;
 
-pub use candlesticks::Candlesticks;
+pub use candlesticks::{Candlesticks, Resolution};
+pub use coingecko::CoinGecko;
 pub use coins::Coins;
 pub use leaderboards::Leaderboards;
 pub use markets::MarketsRegisteredPerDay;
+pub use mempool::Mempool;
 pub use order_history::OrderHistory;
+pub use recent_activity::{MostRecentlyTraded, RecentlyActiveTraders};
 pub use refresh_materialized_view::RefreshMaterializedView;
 pub use rolling_volume::RollingVolume;
 pub use user_history::UserHistory;
+pub use ws::Streaming;
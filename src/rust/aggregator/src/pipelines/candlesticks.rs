@@ -0,0 +1,315 @@
+//! OHLCV candlestick aggregation over raw fills, plus a TradingView Universal
+//! Data Feed (UDF) backend so charting libraries can query Econia directly.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use thiserror::Error;
+
+/// A UDF-supported bar resolution, expressed as the usual TradingView
+/// shorthand (`1`, `5`, `15`, `60`, `240`, `1D`, `1W`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Resolution {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    OneHour,
+    FourHours,
+    OneDay,
+    OneWeek,
+}
+
+impl Resolution {
+    /// All resolutions the `/config` route advertises, in ascending order.
+    pub const ALL: [Resolution; 7] = [
+        Resolution::OneMinute,
+        Resolution::FiveMinutes,
+        Resolution::FifteenMinutes,
+        Resolution::OneHour,
+        Resolution::FourHours,
+        Resolution::OneDay,
+        Resolution::OneWeek,
+    ];
+
+    /// Bucket width in seconds, used to floor a fill timestamp down to its
+    /// bar's `start_time`.
+    pub fn seconds(self) -> i64 {
+        match self {
+            Resolution::OneMinute => 60,
+            Resolution::FiveMinutes => 5 * 60,
+            Resolution::FifteenMinutes => 15 * 60,
+            Resolution::OneHour => 60 * 60,
+            Resolution::FourHours => 4 * 60 * 60,
+            Resolution::OneDay => 24 * 60 * 60,
+            Resolution::OneWeek => 7 * 24 * 60 * 60,
+        }
+    }
+
+    /// The string TradingView sends back in `resolution` query params.
+    pub fn as_udf_str(self) -> &'static str {
+        match self {
+            Resolution::OneMinute => "1",
+            Resolution::FiveMinutes => "5",
+            Resolution::FifteenMinutes => "15",
+            Resolution::OneHour => "60",
+            Resolution::FourHours => "240",
+            Resolution::OneDay => "1D",
+            Resolution::OneWeek => "1W",
+        }
+    }
+
+    pub fn from_udf_str(s: &str) -> Option<Resolution> {
+        Resolution::ALL
+            .into_iter()
+            .find(|r| r.as_udf_str() == s)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CandlesticksError {
+    #[error("unknown resolution: {0}")]
+    UnknownResolution(String),
+    #[error("unknown market id: {0}")]
+    UnknownMarket(i64),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// One OHLCV bar for a given market and resolution, already bucketed by
+/// `start_time = ts - (ts mod resolution_seconds)`.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Bar {
+    pub start_time: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub base_volume: f64,
+    pub quote_volume: f64,
+}
+
+/// UDF `/config` response body.
+#[derive(Debug, Serialize)]
+pub struct UdfConfig {
+    pub supported_resolutions: Vec<&'static str>,
+    pub supports_search: bool,
+    pub supports_group_request: bool,
+    pub supports_marks: bool,
+    pub supports_timescale_marks: bool,
+}
+
+/// UDF `/symbols` response body for a single market.
+///
+/// Per the UDF spec, one tick is `minmov / pricescale` price units, so a
+/// market always renders at `minmov: 1` and a `pricescale` that is the
+/// reciprocal of its tick size, rounded to an integer (e.g. a tick size
+/// of `0.01` is `pricescale: 100`, and `0.25` is `pricescale: 4`).
+#[derive(Debug, Serialize)]
+pub struct SymbolInfo {
+    pub name: String,
+    pub ticker: String,
+    pub description: String,
+    pub pricescale: i64,
+    pub minmov: i64,
+    pub session: &'static str,
+    pub timezone: &'static str,
+    pub has_intraday: bool,
+}
+
+/// UDF `/history` response body, using TradingView's column-array shape.
+#[derive(Debug, Serialize)]
+pub struct UdfHistory {
+    pub t: Vec<i64>,
+    pub o: Vec<f64>,
+    pub h: Vec<f64>,
+    pub l: Vec<f64>,
+    pub c: Vec<f64>,
+    pub v: Vec<f64>,
+    pub s: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_time: Option<i64>,
+}
+
+/// Candlestick aggregation and UDF backend over fills, sourced from the
+/// single `candlesticks` materialized view (one row per market per
+/// 1-minute bucket, the only view the
+/// [`refresh_materialized_view`](crate::pipelines::refresh_materialized_view)
+/// registry actually keeps current). Coarser resolutions are rolled up
+/// from that base view on the fly rather than through their own
+/// materialized views, so there's nothing else to keep refreshed.
+pub struct Candlesticks {
+    pool: PgPool,
+}
+
+impl Candlesticks {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// `/config`
+    pub fn config(&self) -> UdfConfig {
+        UdfConfig {
+            supported_resolutions: Resolution::ALL.iter().map(|r| r.as_udf_str()).collect(),
+            supports_search: false,
+            supports_group_request: false,
+            supports_marks: false,
+            supports_timescale_marks: false,
+        }
+    }
+
+    /// `/symbols?symbol=`, deriving `pricescale`/`minmov` from the market's
+    /// tick size (the smallest price increment, e.g. `0.01`) so
+    /// TradingView renders prices at the market's actual precision.
+    pub async fn symbol_info(
+        &self,
+        market_id: i64,
+        tick_size: f64,
+    ) -> Result<SymbolInfo, CandlesticksError> {
+        let row = sqlx::query!(
+            r#"
+            select base_symbol, quote_symbol
+            from markets
+            where market_id = $1
+            "#,
+            market_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(CandlesticksError::UnknownMarket(market_id))?;
+
+        let name = format!("{}_{}", row.base_symbol, row.quote_symbol);
+        let pricescale = (1.0 / tick_size).round().max(1.0) as i64;
+        Ok(SymbolInfo {
+            ticker: name.clone(),
+            name,
+            description: format!("{}/{}", row.base_symbol, row.quote_symbol),
+            pricescale,
+            minmov: 1,
+            session: "24x7",
+            timezone: "Etc/UTC",
+            has_intraday: true,
+        })
+    }
+
+    /// `/history?symbol&resolution&from&to`: reads the base `candlesticks`
+    /// view at its native 1-minute granularity and, for any coarser
+    /// `resolution`, rolls those bars up on the fly by flooring each
+    /// bucket's `start_time` to the resolution width and re-aggregating
+    /// open/high/low/close/volume across it.
+    pub async fn history(
+        &self,
+        market_id: i64,
+        resolution: Resolution,
+        from: i64,
+        to: i64,
+    ) -> Result<UdfHistory, CandlesticksError> {
+        let resolution_seconds = resolution.seconds();
+        let bars: Vec<Bar> = sqlx::query_as(
+            r#"
+            select
+                (start_time - start_time % $1) as start_time,
+                (array_agg(open order by start_time asc))[1] as open,
+                max(high) as high,
+                min(low) as low,
+                (array_agg(close order by start_time desc))[1] as close,
+                sum(base_volume) as base_volume,
+                sum(quote_volume) as quote_volume
+            from candlesticks
+            where market_id = $2 and start_time >= $3 and start_time < $4
+            group by 1
+            order by 1 asc
+            "#,
+        )
+        .bind(resolution_seconds)
+        .bind(market_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        if bars.is_empty() {
+            // Prefer pointing the client at the most recent bar before the
+            // window (so an out-of-range `from` pages backward toward real
+            // data); fall back to the next bar after `to` only if there's
+            // nothing earlier at all.
+            let prior = sqlx::query_scalar::<_, Option<i64>>(
+                "select max(start_time - start_time % $1) from candlesticks \
+                 where market_id = $2 and start_time < $3",
+            )
+            .bind(resolution_seconds)
+            .bind(market_id)
+            .bind(from)
+            .fetch_one(&self.pool)
+            .await?;
+
+            let next_time = match prior {
+                Some(_) => prior,
+                None => {
+                    sqlx::query_scalar::<_, Option<i64>>(
+                        "select min(start_time - start_time % $1) from candlesticks \
+                         where market_id = $2 and start_time >= $3",
+                    )
+                    .bind(resolution_seconds)
+                    .bind(market_id)
+                    .bind(to)
+                    .fetch_one(&self.pool)
+                    .await?
+                }
+            };
+
+            return Ok(UdfHistory {
+                t: vec![],
+                o: vec![],
+                h: vec![],
+                l: vec![],
+                c: vec![],
+                v: vec![],
+                s: "no_data",
+                next_time,
+            });
+        }
+
+        let mut history = UdfHistory {
+            t: Vec::with_capacity(bars.len()),
+            o: Vec::with_capacity(bars.len()),
+            h: Vec::with_capacity(bars.len()),
+            l: Vec::with_capacity(bars.len()),
+            c: Vec::with_capacity(bars.len()),
+            v: Vec::with_capacity(bars.len()),
+            s: "ok",
+            next_time: None,
+        };
+        for bar in bars {
+            history.t.push(bar.start_time);
+            history.o.push(bar.open);
+            history.h.push(bar.high);
+            history.l.push(bar.low);
+            history.c.push(bar.close);
+            history.v.push(bar.base_volume);
+        }
+        Ok(history)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolution_round_trips_through_udf_str() {
+        for resolution in Resolution::ALL {
+            let s = resolution.as_udf_str();
+            assert_eq!(Resolution::from_udf_str(s), Some(resolution));
+        }
+    }
+
+    #[test]
+    fn resolution_seconds_are_strictly_increasing() {
+        let seconds: Vec<i64> = Resolution::ALL.iter().map(|r| r.seconds()).collect();
+        assert!(seconds.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn unknown_resolution_string_is_rejected() {
+        assert!(Resolution::from_udf_str("banana").is_none());
+    }
+}
@@ -0,0 +1,130 @@
+//! Ticker and order-book views matching the CoinGecko/CoinMarketCap
+//! exchange integration spec, built on top of [`MarketsRegisteredPerDay`]
+//! and [`RollingVolume`] so the heavy lifting is joins rather than new
+//! indexing.
+
+use serde::Serialize;
+use sqlx::PgPool;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CoinGeckoError {
+    #[error("unknown market id: {0}")]
+    UnknownMarket(i64),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// One row of the `/tickers` response.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Ticker {
+    pub ticker_id: String,
+    pub base_currency: String,
+    pub target_currency: String,
+    pub last_price: f64,
+    pub base_volume: f64,
+    pub target_volume: f64,
+    pub bid: f64,
+    pub ask: f64,
+    pub high: f64,
+    pub low: f64,
+}
+
+/// A single price level in an `/orderbook` response.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Level {
+    pub price: f64,
+    pub size: f64,
+}
+
+/// `/orderbook?ticker_id&depth` response body.
+#[derive(Debug, Serialize)]
+pub struct Orderbook {
+    pub ticker_id: String,
+    pub bids: Vec<[f64; 2]>,
+    pub asks: Vec<[f64; 2]>,
+}
+
+/// CoinGecko/CMC listing-aggregator endpoints.
+pub struct CoinGecko {
+    pool: PgPool,
+}
+
+impl CoinGecko {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// `/tickers`: one row per registered market, joining the market's
+    /// base/quote symbols with its rolling 24h volume and best bid/ask.
+    pub async fn tickers(&self) -> Result<Vec<Ticker>, CoinGeckoError> {
+        let tickers = sqlx::query_as!(
+            Ticker,
+            r#"
+            select
+                m.base_symbol || '_' || m.quote_symbol as "ticker_id!",
+                m.base_symbol as "base_currency!",
+                m.quote_symbol as "target_currency!",
+                rv.last_price as "last_price!",
+                rv.base_volume as "base_volume!",
+                rv.quote_volume as "target_volume!",
+                rv.best_bid as "bid!",
+                rv.best_ask as "ask!",
+                rv.high_24h as "high!",
+                rv.low_24h as "low!"
+            from markets m
+            join rolling_volume rv on rv.market_id = m.market_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(tickers)
+    }
+
+    /// `/orderbook?ticker_id&depth`: open orders for the market, aggregated
+    /// by price level and truncated to `depth` levels per side.
+    pub async fn orderbook(
+        &self,
+        market_id: i64,
+        ticker_id: String,
+        depth: i64,
+    ) -> Result<Orderbook, CoinGeckoError> {
+        let bids: Vec<Level> = sqlx::query_as!(
+            Level,
+            r#"
+            select price as "price!", sum(size) as "size!"
+            from open_orders
+            where market_id = $1 and side = 'bid'
+            group by price
+            order by price desc
+            limit $2
+            "#,
+            market_id,
+            depth,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let asks: Vec<Level> = sqlx::query_as!(
+            Level,
+            r#"
+            select price as "price!", sum(size) as "size!"
+            from open_orders
+            where market_id = $1 and side = 'ask'
+            group by price
+            order by price asc
+            limit $2
+            "#,
+            market_id,
+            depth,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Orderbook {
+            ticker_id,
+            bids: bids.into_iter().map(|l| [l.price, l.size]).collect(),
+            asks: asks.into_iter().map(|l| [l.price, l.size]).collect(),
+        })
+    }
+}
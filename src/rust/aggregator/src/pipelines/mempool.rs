@@ -0,0 +1,159 @@
+//! Pending/mempool order view, parallel to the finalized
+//! [`order_history`](crate::pipelines::order_history) and
+//! [`user_history`](crate::pipelines::user_history) models, so UIs can
+//! optimistically reflect in-flight user actions before the indexer has
+//! caught up to them.
+//!
+//! Entries are keyed by the submitting account's pending transaction
+//! sequence number and reconciled once the corresponding confirmed row
+//! appears in `user_history`: a matching sequence number
+//! means the action landed and the entry is dropped, while an entry whose
+//! TTL elapses without a match is evicted as stale.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MempoolError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// The action a pending entry represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum PendingKind {
+    Place,
+    Cancel,
+}
+
+/// A submitted-but-not-yet-finalized order or cancellation.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PendingEntry {
+    pub account_address: String,
+    pub sequence_number: i64,
+    pub market_id: i64,
+    pub kind: PendingKind,
+    pub submitted_at: DateTime<Utc>,
+}
+
+/// Tracks in-flight orders and cancellations until the indexer's confirmed
+/// rows catch up to them.
+pub struct Mempool {
+    pool: PgPool,
+    ttl_seconds: i64,
+}
+
+impl Mempool {
+    /// `ttl_seconds` bounds how long an entry is surfaced before it's
+    /// evicted as stale, i.e. the longest we wait for a submitted
+    /// transaction to either land or be dropped by the chain.
+    pub fn new(pool: PgPool, ttl_seconds: i64) -> Self {
+        Self { pool, ttl_seconds }
+    }
+
+    /// Records a newly-submitted order or cancellation before it's
+    /// confirmed.
+    pub async fn record(
+        &self,
+        account_address: &str,
+        sequence_number: i64,
+        market_id: i64,
+        kind: PendingKind,
+    ) -> Result<(), MempoolError> {
+        sqlx::query!(
+            r#"
+            insert into mempool_entries
+                (account_address, sequence_number, market_id, kind, submitted_at)
+            values ($1, $2, $3, $4, now())
+            on conflict (account_address, sequence_number) do nothing
+            "#,
+            account_address,
+            sequence_number,
+            market_id,
+            kind as PendingKind,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Pending entries for a market, excluding anything past its TTL.
+    pub async fn for_market(&self, market_id: i64) -> Result<Vec<PendingEntry>, MempoolError> {
+        let entries = sqlx::query_as!(
+            PendingEntry,
+            r#"
+            select
+                account_address as "account_address!",
+                sequence_number as "sequence_number!",
+                market_id as "market_id!",
+                kind as "kind: PendingKind",
+                submitted_at as "submitted_at!"
+            from mempool_entries
+            where market_id = $1 and submitted_at > now() - make_interval(secs => $2)
+            order by submitted_at asc
+            "#,
+            market_id,
+            self.ttl_seconds as f64,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(entries)
+    }
+
+    /// Pending entries for a user, excluding anything past its TTL.
+    pub async fn for_user(&self, account_address: &str) -> Result<Vec<PendingEntry>, MempoolError> {
+        let entries = sqlx::query_as!(
+            PendingEntry,
+            r#"
+            select
+                account_address as "account_address!",
+                sequence_number as "sequence_number!",
+                market_id as "market_id!",
+                kind as "kind: PendingKind",
+                submitted_at as "submitted_at!"
+            from mempool_entries
+            where account_address = $1 and submitted_at > now() - make_interval(secs => $2)
+            order by submitted_at asc
+            "#,
+            account_address,
+            self.ttl_seconds as f64,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(entries)
+    }
+
+    /// Drops entries whose `(account_address, sequence_number)` now has a
+    /// matching confirmed row in `user_history`, and separately evicts
+    /// anything past its TTL that never landed. Intended to run on the
+    /// same cadence as the indexer's ingestion loop.
+    pub async fn reconcile(&self) -> Result<u64, MempoolError> {
+        let confirmed = sqlx::query!(
+            r#"
+            delete from mempool_entries me
+            using user_history uh
+            where uh.account_address = me.account_address
+              and uh.sequence_number = me.sequence_number
+            "#,
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        let expired = sqlx::query!(
+            r#"
+            delete from mempool_entries
+            where submitted_at <= now() - make_interval(secs => $1)
+            "#,
+            self.ttl_seconds as f64,
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(confirmed + expired)
+    }
+}
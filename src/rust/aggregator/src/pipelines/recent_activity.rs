@@ -0,0 +1,126 @@
+//! Recency-based discovery queries, complementing the point-in-time
+//! [`Leaderboards`](crate::pipelines::Leaderboards) model (which ranks by
+//! volume) with "what's happening right now" feeds a frontend can use to
+//! build "trending markets" and "active traders" panels.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RecentActivityError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// A market ordered by the timestamp of its latest fill.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct RecentlyTradedMarket {
+    pub market_id: i64,
+    pub last_fill_time: DateTime<Utc>,
+    pub fills_in_window: i64,
+}
+
+/// An address ordered by its last order or fill time within the query
+/// window.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ActiveTrader {
+    pub account_address: String,
+    pub last_active_time: DateTime<Utc>,
+    pub fills_in_window: i64,
+    pub markets_touched: i64,
+}
+
+/// Cursor-paginated "most recently traded markets" feed.
+pub struct MostRecentlyTraded {
+    pool: PgPool,
+}
+
+impl MostRecentlyTraded {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Markets ordered by most-recent fill first, counting fills within
+    /// `since`, paging with `from` (an exclusive `last_fill_time` cursor
+    /// applied to each market's *aggregated* last-fill time, not to
+    /// individual fills — otherwise a market with both older and newer
+    /// fills would be clipped to its older ones and reappear under a
+    /// different `last_fill_time` on a later page) and `limit`.
+    pub async fn page(
+        &self,
+        since: DateTime<Utc>,
+        from: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<RecentlyTradedMarket>, RecentActivityError> {
+        let markets = sqlx::query_as!(
+            RecentlyTradedMarket,
+            r#"
+            select
+                market_id as "market_id!",
+                max(time) as "last_fill_time!",
+                count(*) as "fills_in_window!"
+            from fills
+            where time >= $1
+            group by market_id
+            having $2::timestamptz is null or max(time) < $2
+            order by last_fill_time desc
+            limit $3
+            "#,
+            since,
+            from,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(markets)
+    }
+}
+
+/// Cursor-paginated "recently active traders" feed over `user_history`.
+pub struct RecentlyActiveTraders {
+    pool: PgPool,
+}
+
+impl RecentlyActiveTraders {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Addresses ordered by most-recent order/fill first, within
+    /// `since`, paging with `from` (an exclusive `last_active_time`
+    /// cursor applied to each address's *aggregated* last-active time, not
+    /// to individual rows — otherwise a trader active both before and
+    /// after the cursor would be clipped to their older rows and reappear
+    /// under a different `last_active_time` on a later page) and `limit`.
+    pub async fn page(
+        &self,
+        since: DateTime<Utc>,
+        from: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<ActiveTrader>, RecentActivityError> {
+        let traders = sqlx::query_as!(
+            ActiveTrader,
+            r#"
+            select
+                account_address as "account_address!",
+                max(time) as "last_active_time!",
+                count(*) as "fills_in_window!",
+                count(distinct market_id) as "markets_touched!"
+            from user_history
+            where time >= $1
+            group by account_address
+            having $2::timestamptz is null or max(time) < $2
+            order by last_active_time desc
+            limit $3
+            "#,
+            since,
+            from,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(traders)
+    }
+}
@@ -0,0 +1,266 @@
+//! Scheduler that owns a registry of refreshable materialized views and
+//! runs `REFRESH MATERIALIZED VIEW CONCURRENTLY` on each view's own
+//! cadence, in dependency order, instead of relying on an external cron
+//! job driving one-shot refreshes.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+#[derive(Debug, Error)]
+pub enum RefreshError {
+    #[error("unregistered view: {0}")]
+    UnregisteredView(String),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// A refreshable view and the views it must wait on, so e.g. `leaderboards`
+/// (built on `rolling_volume`) only refreshes after its input is current.
+#[derive(Debug, Clone)]
+pub struct ViewSpec {
+    pub name: &'static str,
+    pub interval: Duration,
+    pub depends_on: Vec<&'static str>,
+}
+
+/// Last-run bookkeeping for a single view, exposed via the admin status
+/// endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub view: String,
+    pub last_started_at: Option<i64>,
+    pub last_duration_ms: Option<u64>,
+    pub last_error: Option<String>,
+    pub running: bool,
+}
+
+struct JobState {
+    spec: ViewSpec,
+    status: JobStatus,
+    next_due: Instant,
+    running: bool,
+}
+
+/// Background job queue that refreshes each registered view on its own
+/// interval. Refreshes of the same view are serialized — if one is still
+/// running when the interval elapses again, the tick is skipped rather
+/// than stacking a second refresh behind it.
+pub struct RefreshMaterializedView {
+    pool: PgPool,
+    jobs: Arc<Mutex<HashMap<&'static str, JobState>>>,
+}
+
+impl RefreshMaterializedView {
+    /// Builds the default registry: `rolling_volume` and `markets` refresh
+    /// independently, `candlesticks` follows `rolling_volume` for its
+    /// volume columns, and `leaderboards` follows `rolling_volume` for its
+    /// ranking.
+    pub fn new(pool: PgPool) -> Self {
+        let specs = vec![
+            ViewSpec {
+                name: "rolling_volume",
+                interval: Duration::from_secs(60),
+                depends_on: vec![],
+            },
+            ViewSpec {
+                name: "markets",
+                interval: Duration::from_secs(300),
+                depends_on: vec![],
+            },
+            ViewSpec {
+                name: "candlesticks",
+                interval: Duration::from_secs(60),
+                depends_on: vec!["rolling_volume"],
+            },
+            ViewSpec {
+                name: "leaderboards",
+                interval: Duration::from_secs(300),
+                depends_on: vec!["rolling_volume"],
+            },
+        ];
+        Self::with_specs(pool, specs)
+    }
+
+    pub fn with_specs(pool: PgPool, specs: Vec<ViewSpec>) -> Self {
+        let now = Instant::now();
+        let jobs = specs
+            .into_iter()
+            .map(|spec| {
+                let status = JobStatus {
+                    view: spec.name.to_string(),
+                    last_started_at: None,
+                    last_duration_ms: None,
+                    last_error: None,
+                    running: false,
+                };
+                (
+                    spec.name,
+                    JobState {
+                        spec,
+                        status,
+                        next_due: now,
+                        running: false,
+                    },
+                )
+            })
+            .collect();
+        Self {
+            pool,
+            jobs: Arc::new(Mutex::new(jobs)),
+        }
+    }
+
+    /// Runs one scheduling tick: refreshes every due view whose
+    /// dependencies aren't currently running, skipping any view that is
+    /// still mid-refresh from a previous tick. Due views are refreshed in
+    /// dependency order so e.g. `leaderboards` never runs before
+    /// `rolling_volume` within the same tick just because both came due at
+    /// once.
+    ///
+    /// A view whose refresh fails doesn't stop the tick — its error is
+    /// already captured in its own [`JobStatus::last_error`] by
+    /// [`Self::refresh`], and the remaining due, independent views still
+    /// need to run on schedule.
+    pub async fn tick(&self) {
+        let now = Instant::now();
+        let due: Vec<(&'static str, Vec<&'static str>)> = {
+            let jobs = self.jobs.lock().await;
+            jobs.values()
+                .filter(|job| !job.running && job.next_due <= now)
+                .filter(|job| {
+                    job.spec
+                        .depends_on
+                        .iter()
+                        .all(|dep| jobs.get(dep).map_or(true, |d| !d.running))
+                })
+                .map(|job| (job.spec.name, job.spec.depends_on.clone()))
+                .collect()
+        };
+
+        for view in topological_order(due) {
+            let _ = self.refresh(view).await;
+        }
+    }
+
+    /// Refreshes a single view out-of-band, bypassing the interval check.
+    /// Used by the admin "trigger refresh" endpoint.
+    pub async fn refresh(&self, view: &'static str) -> Result<(), RefreshError> {
+        {
+            let mut jobs = self.jobs.lock().await;
+            let job = jobs
+                .get_mut(view)
+                .ok_or(RefreshError::UnregisteredView(view.to_string()))?;
+            if job.running {
+                return Ok(());
+            }
+            job.running = true;
+            job.status.running = true;
+            job.status.last_started_at = Some(Utc::now().timestamp());
+        }
+
+        let start = Instant::now();
+        let result = sqlx::query(&format!("REFRESH MATERIALIZED VIEW CONCURRENTLY {view}"))
+            .execute(&self.pool)
+            .await;
+        let duration = start.elapsed();
+
+        let mut jobs = self.jobs.lock().await;
+        if let Some(job) = jobs.get_mut(view) {
+            job.running = false;
+            job.status.running = false;
+            job.status.last_duration_ms = Some(duration.as_millis() as u64);
+            job.status.last_error = result.as_ref().err().map(|e| e.to_string());
+            job.next_due = Instant::now() + job.spec.interval;
+        }
+        result.map(|_| ()).map_err(RefreshError::from)
+    }
+
+    /// Status of every registered view, for the admin status endpoint.
+    pub async fn status(&self) -> Vec<JobStatus> {
+        self.jobs
+            .lock()
+            .await
+            .values()
+            .map(|job| job.status.clone())
+            .collect()
+    }
+}
+
+/// Orders a tick's due views so that a view is never scheduled before any
+/// of its own `depends_on` entries that are also due this tick. Ties (and,
+/// defensively, any dependency cycle) are broken by falling back to the
+/// views' original relative order rather than stalling.
+fn topological_order(due: Vec<(&'static str, Vec<&'static str>)>) -> Vec<&'static str> {
+    let due_names: Vec<&'static str> = due.iter().map(|(name, _)| *name).collect();
+    let mut remaining = due;
+    let mut ordered: Vec<&'static str> = Vec::with_capacity(remaining.len());
+
+    while !remaining.is_empty() {
+        let ready_index = remaining.iter().position(|(_, depends_on)| {
+            depends_on
+                .iter()
+                .all(|dep| !due_names.contains(dep) || ordered.contains(dep))
+        });
+        let (name, _) = remaining.remove(ready_index.unwrap_or(0));
+        ordered.push(name);
+    }
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specs() -> Vec<ViewSpec> {
+        vec![
+            ViewSpec {
+                name: "rolling_volume",
+                interval: Duration::from_secs(1),
+                depends_on: vec![],
+            },
+            ViewSpec {
+                name: "leaderboards",
+                interval: Duration::from_secs(1),
+                depends_on: vec!["rolling_volume"],
+            },
+        ]
+    }
+
+    #[test]
+    fn dependency_names_must_reference_registered_views() {
+        let registered: Vec<&'static str> = specs().iter().map(|s| s.name).collect();
+        for spec in specs() {
+            for dep in spec.depends_on {
+                assert!(registered.contains(&dep));
+            }
+        }
+    }
+
+    #[test]
+    fn topological_order_refreshes_dependencies_before_dependents() {
+        let due = vec![
+            ("leaderboards", vec!["rolling_volume"]),
+            ("rolling_volume", vec![]),
+        ];
+        let order = topological_order(due);
+        let rolling_volume_index = order.iter().position(|v| *v == "rolling_volume").unwrap();
+        let leaderboards_index = order.iter().position(|v| *v == "leaderboards").unwrap();
+        assert!(rolling_volume_index < leaderboards_index);
+    }
+
+    #[test]
+    fn topological_order_is_unaffected_by_input_order() {
+        let due = vec![("rolling_volume", vec![]), ("leaderboards", vec!["rolling_volume"])];
+        assert_eq!(topological_order(due), vec!["rolling_volume", "leaderboards"]);
+        let reversed = vec![("leaderboards", vec!["rolling_volume"]), ("rolling_volume", vec![])];
+        assert_eq!(topological_order(reversed), vec!["rolling_volume", "leaderboards"]);
+    }
+}
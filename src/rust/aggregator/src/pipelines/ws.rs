@@ -0,0 +1,409 @@
+//! Streaming subsystem pushing incremental updates derived from the
+//! [`candlesticks`](crate::pipelines::candlesticks),
+//! [`order_history`](crate::pipelines::order_history) and
+//! [`user_history`](crate::pipelines::user_history) models, so clients
+//! don't have to poll the REST routes for fresh data.
+//!
+//! Clients subscribe to a [`Channel`]; the server tails new rows for that
+//! channel with a polling cursor over monotonically increasing event ids
+//! (there is no `LISTEN/NOTIFY` path) and emits a [`Message`] per changed
+//! row. Every message carries
+//! a `sequence` so a client that detects a gap can fall back to the REST
+//! snapshot and resume streaming from there.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::sync::{broadcast, Mutex};
+
+use crate::pipelines::candlesticks::{Bar, Resolution};
+
+#[derive(Debug, Error)]
+pub enum StreamingError {
+    #[error("malformed channel: {0}")]
+    MalformedChannel(String),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// A subscribable channel, parsed from its wire form (e.g.
+/// `candlesticks:3:1D`, `fills:3`, `orders:0xabc...`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Channel {
+    Candlesticks { market_id: i64, resolution: Resolution },
+    Fills { market_id: i64 },
+    Orders { user_address: String },
+}
+
+impl Channel {
+    pub fn parse(raw: &str) -> Result<Channel, StreamingError> {
+        let parts: Vec<&str> = raw.split(':').collect();
+        match parts.as_slice() {
+            ["candlesticks", market_id, resolution] => {
+                let market_id = market_id
+                    .parse()
+                    .map_err(|_| StreamingError::MalformedChannel(raw.to_string()))?;
+                let resolution = Resolution::from_udf_str(resolution)
+                    .ok_or_else(|| StreamingError::MalformedChannel(raw.to_string()))?;
+                Ok(Channel::Candlesticks { market_id, resolution })
+            }
+            ["fills", market_id] => {
+                let market_id = market_id
+                    .parse()
+                    .map_err(|_| StreamingError::MalformedChannel(raw.to_string()))?;
+                Ok(Channel::Fills { market_id })
+            }
+            ["orders", user_address] => Ok(Channel::Orders {
+                user_address: (*user_address).to_string(),
+            }),
+            _ => Err(StreamingError::MalformedChannel(raw.to_string())),
+        }
+    }
+
+    /// The canonical wire form, also used as the key under which this
+    /// channel's broadcast stream and sequence counter are tracked.
+    pub fn to_key(&self) -> String {
+        match self {
+            Channel::Candlesticks { market_id, resolution } => {
+                format!("candlesticks:{}:{}", market_id, resolution.as_udf_str())
+            }
+            Channel::Fills { market_id } => format!("fills:{market_id}"),
+            Channel::Orders { user_address } => format!("orders:{user_address}"),
+        }
+    }
+}
+
+/// Whether a candlestick update closes out its bucket. A bucket transitions
+/// `Update` -> `Update` -> ... -> `Close` once a later fill opens the next
+/// bucket, so charts know when to finalize the bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BarEvent {
+    Update,
+    Close,
+}
+
+/// A single message pushed to subscribers of a channel.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    Candle {
+        sequence: u64,
+        market_id: i64,
+        resolution: &'static str,
+        event: BarEvent,
+        bar: BarPayload,
+    },
+    Fill {
+        sequence: u64,
+        market_id: i64,
+        fill: FillPayload,
+    },
+    Order {
+        sequence: u64,
+        user_address: String,
+        order: OrderPayload,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BarPayload {
+    pub t: i64,
+    pub o: f64,
+    pub h: f64,
+    pub l: f64,
+    pub c: f64,
+    pub v: f64,
+}
+
+impl From<Bar> for BarPayload {
+    fn from(bar: Bar) -> Self {
+        BarPayload {
+            t: bar.start_time,
+            o: bar.open,
+            h: bar.high,
+            l: bar.low,
+            c: bar.close,
+            v: bar.base_volume,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct FillPayload {
+    pub price: f64,
+    pub size: f64,
+    pub taker_side: String,
+    pub time: i64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct OrderPayload {
+    pub order_id: i64,
+    pub market_id: i64,
+    pub status: String,
+    pub time: i64,
+}
+
+/// Per-channel broadcast state: its own stream (so a subscriber to
+/// `fills:3` never sees `fills:7` traffic) and its own sequence counter
+/// (so a gap in one channel's numbering doesn't show up as a gap in
+/// another's).
+struct ChannelState {
+    sender: broadcast::Sender<Message>,
+    next_sequence: u64,
+    /// For `Channel::Candlesticks` channels only: the `start_time` of the
+    /// most recent bucket that has already been emitted as
+    /// [`BarEvent::Close`], so closed buckets aren't re-announced on every
+    /// poll. `None` until the first bucket closes.
+    candles_closed_through: Option<i64>,
+}
+
+impl ChannelState {
+    fn new(buffer: usize) -> Self {
+        let (sender, _) = broadcast::channel(buffer);
+        Self {
+            sender,
+            next_sequence: 0,
+            candles_closed_through: None,
+        }
+    }
+}
+
+/// Owns a broadcast stream per subscribed [`Channel`] and the polling
+/// cursors used to tail new rows for each. [`Streaming::poll_fills`],
+/// [`Streaming::poll_orders`] and [`Streaming::poll_candles`] each publish
+/// whatever they find when called; this type spawns no task of its own to
+/// call them on an interval. Subscribers that fall behind their channel's broadcast buffer see
+/// a [`broadcast::error::RecvError::Lagged`] and are expected to re-fetch
+/// the REST snapshot before resubscribing.
+pub struct Streaming {
+    pool: PgPool,
+    buffer: usize,
+    channels: Mutex<HashMap<String, ChannelState>>,
+}
+
+impl Streaming {
+    pub fn new(pool: PgPool, buffer: usize) -> Self {
+        Self {
+            pool,
+            buffer,
+            channels: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Subscribes to one channel's stream, creating it if this is the
+    /// first subscriber.
+    pub async fn subscribe(&self, channel: &Channel) -> broadcast::Receiver<Message> {
+        let mut channels = self.channels.lock().await;
+        channels
+            .entry(channel.to_key())
+            .or_insert_with(|| ChannelState::new(self.buffer))
+            .sender
+            .subscribe()
+    }
+
+    /// Publishes one message on `channel`, stamping it with that channel's
+    /// next sequence number. No-op (beyond advancing the sequence) if
+    /// nobody is currently subscribed.
+    async fn publish(&self, channel: &Channel, build: impl FnOnce(u64) -> Message) {
+        let mut channels = self.channels.lock().await;
+        let state = channels
+            .entry(channel.to_key())
+            .or_insert_with(|| ChannelState::new(self.buffer));
+        state.next_sequence += 1;
+        let _ = state.sender.send(build(state.next_sequence));
+    }
+
+    /// Tails new fills since `since_event_id` on the `fills:{market_id}`
+    /// channel, returning the highest event id seen so the caller can
+    /// advance its cursor.
+    pub async fn poll_fills(
+        &self,
+        market_id: i64,
+        since_event_id: i64,
+    ) -> Result<i64, StreamingError> {
+        let fills: Vec<(i64, FillPayload)> = sqlx::query_as(
+            r#"
+            select event_id, price, size, taker_side, time
+            from fills
+            where market_id = $1 and event_id > $2
+            order by event_id asc
+            "#,
+        )
+        .bind(market_id)
+        .bind(since_event_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let channel = Channel::Fills { market_id };
+        let mut max_event_id = since_event_id;
+        for (event_id, fill) in fills {
+            max_event_id = max_event_id.max(event_id);
+            self.publish(&channel, |sequence| Message::Fill {
+                sequence,
+                market_id,
+                fill,
+            })
+            .await;
+        }
+        Ok(max_event_id)
+    }
+
+    /// Tails new orders/cancellations for a user since `since_event_id` on
+    /// the `orders:{user_address}` channel, returning the highest event id
+    /// seen so the caller can advance its cursor.
+    pub async fn poll_orders(
+        &self,
+        user_address: &str,
+        since_event_id: i64,
+    ) -> Result<i64, StreamingError> {
+        let orders: Vec<(i64, OrderPayload)> = sqlx::query_as(
+            r#"
+            select event_id, order_id, market_id, status, time
+            from user_history
+            where account_address = $1 and event_id > $2
+            order by event_id asc
+            "#,
+        )
+        .bind(user_address)
+        .bind(since_event_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let channel = Channel::Orders {
+            user_address: user_address.to_string(),
+        };
+        let mut max_event_id = since_event_id;
+        for (event_id, order) in orders {
+            max_event_id = max_event_id.max(event_id);
+            self.publish(&channel, |sequence| Message::Order {
+                sequence,
+                user_address: user_address.to_string(),
+                order,
+            })
+            .await;
+        }
+        Ok(max_event_id)
+    }
+
+    /// Tails the `candlesticks` view for `market_id`/`resolution`, rolling
+    /// 1-minute base rows up to `resolution` the same way
+    /// [`Candlesticks::history`](crate::pipelines::candlesticks::Candlesticks::history)
+    /// does. Every bucket still being filled is republished as
+    /// [`BarEvent::Update`] on every poll; once a later bucket appears,
+    /// the earlier one is republished exactly once more as
+    /// [`BarEvent::Close`] so charts finalize it.
+    pub async fn poll_candles(
+        &self,
+        market_id: i64,
+        resolution: Resolution,
+    ) -> Result<(), StreamingError> {
+        let channel = Channel::Candlesticks { market_id, resolution };
+        let closed_through = {
+            let channels = self.channels.lock().await;
+            channels
+                .get(&channel.to_key())
+                .and_then(|c| c.candles_closed_through)
+        };
+
+        let resolution_seconds = resolution.seconds();
+        let bars: Vec<Bar> = sqlx::query_as(
+            r#"
+            select
+                (start_time - start_time % $1) as start_time,
+                (array_agg(open order by start_time asc))[1] as open,
+                max(high) as high,
+                min(low) as low,
+                (array_agg(close order by start_time desc))[1] as close,
+                sum(base_volume) as base_volume,
+                sum(quote_volume) as quote_volume
+            from candlesticks
+            where market_id = $2 and (start_time - start_time % $1) > $3
+            group by 1
+            order by 1 asc
+            "#,
+        )
+        .bind(resolution_seconds)
+        .bind(market_id)
+        .bind(closed_through.unwrap_or(0))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let Some(last_index) = bars.len().checked_sub(1) else {
+            return Ok(());
+        };
+
+        let resolution_str = resolution.as_udf_str();
+        let mut newly_closed_through = closed_through;
+        for (i, bar) in bars.into_iter().enumerate() {
+            let event = if i == last_index { BarEvent::Update } else { BarEvent::Close };
+            if event == BarEvent::Close {
+                newly_closed_through = Some(bar.start_time);
+            }
+            let bar_payload = BarPayload::from(bar);
+            self.publish(&channel, |sequence| Message::Candle {
+                sequence,
+                market_id,
+                resolution: resolution_str,
+                event,
+                bar: bar_payload,
+            })
+            .await;
+        }
+
+        if newly_closed_through != closed_through {
+            let mut channels = self.channels.lock().await;
+            channels
+                .entry(channel.to_key())
+                .or_insert_with(|| ChannelState::new(self.buffer))
+                .candles_closed_through = newly_closed_through;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_candlestick_channel() {
+        assert_eq!(
+            Channel::parse("candlesticks:3:1D").unwrap(),
+            Channel::Candlesticks {
+                market_id: 3,
+                resolution: Resolution::OneDay,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_fills_channel() {
+        assert_eq!(Channel::parse("fills:7").unwrap(), Channel::Fills { market_id: 7 });
+    }
+
+    #[test]
+    fn parses_orders_channel() {
+        assert_eq!(
+            Channel::parse("orders:0xabc").unwrap(),
+            Channel::Orders {
+                user_address: "0xabc".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_channel() {
+        assert!(Channel::parse("candlesticks:not-a-number:1D").is_err());
+        assert!(Channel::parse("unknown:3").is_err());
+    }
+
+    #[test]
+    fn channel_key_round_trips_through_parse() {
+        let raw = "candlesticks:3:1D";
+        assert_eq!(Channel::parse(raw).unwrap().to_key(), raw);
+    }
+}